@@ -0,0 +1,221 @@
+//! Pluggable transaction generators.
+//!
+//! `generate_transactions` in `main` picks a generator by name and drives it
+//! to produce the final RPC batch. A generator only needs a snapshot of each
+//! account's starting balance to size its candidate values; it doesn't see
+//! balance changes applied as transactions are accepted, since
+//! `generate_transactions` owns that bookkeeping (and the mutable borrow of
+//! the live accounts that comes with it). The `TransactionGenerator` trait is
+//! just a name for "anything that yields `(from, to, value)`", so new
+//! generators slot in without touching the dispatch code beyond the `match`
+//! in `main`.
+
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use {Account, AccountId};
+
+/// Free-form `--gen-param key=value` pairs, parsed by whichever generator
+/// cares about them.
+pub type GeneratorParams = HashMap<String, String>;
+
+fn param_f64(params: &GeneratorParams, key: &str, default: f64) -> f64 {
+    params.get(key)
+        .map(|v| v.parse().unwrap_or_else(|_| panic!("--gen-param {}: expected a number, got {:?}", key, v)))
+        .unwrap_or(default)
+}
+
+fn param_usize(params: &GeneratorParams, key: &str, default: usize) -> usize {
+    params.get(key)
+        .map(|v| v.parse().unwrap_or_else(|_| panic!("--gen-param {}: expected a number, got {:?}", key, v)))
+        .unwrap_or(default)
+}
+
+fn snapshot(accounts: &[Account]) -> Vec<(AccountId, u64)> {
+    accounts.iter().map(|account| (account.id.clone(), account.balance)).collect()
+}
+
+/// A source of `(from, to, value)` transfers over the configured accounts.
+/// Any iterator with this `Item` qualifies; this is a marker, not a trait
+/// with its own methods, so existing and future generators need no special
+/// plumbing beyond implementing `Iterator`.
+pub trait TransactionGenerator: Iterator<Item = (AccountId, AccountId, u64)> {}
+
+impl<T> TransactionGenerator for T where T: Iterator<Item = (AccountId, AccountId, u64)> {}
+
+/// Picks two distinct accounts at random and transfers a random amount up to
+/// twice the sender's starting balance (it does not check the sender can
+/// still afford it; `generate_transactions` is responsible for rejecting
+/// overdrafts).
+pub struct RandomTransactions<'b, R: 'b> {
+    accounts: Vec<(AccountId, u64)>,
+    rng: &'b mut R,
+}
+
+impl<'b, R: Rng> RandomTransactions<'b, R> {
+    pub fn new(accounts: &[Account], rng: &'b mut R) -> Self {
+        RandomTransactions { accounts: snapshot(accounts), rng }
+    }
+}
+
+impl<'b, R: Rng> Iterator for RandomTransactions<'b, R> {
+    type Item = (AccountId, AccountId, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let n = self.accounts.len();
+        if n < 2 {
+            return None;
+        }
+
+        let from_idx = self.rng.gen_range(0, n);
+        let to_idx = loop {
+            let candidate = self.rng.gen_range(0, n);
+            if candidate != from_idx {
+                break candidate;
+            }
+        };
+
+        let max_value = 2 * self.accounts[from_idx].1 + 1;
+        let value = self.rng.gen_range(0, max_value);
+
+        Some((self.accounts[from_idx].0.clone(), self.accounts[to_idx].0.clone(), value))
+    }
+}
+
+/// A fixed "winner" account that every other account sends a small, steady
+/// trickle of value to, modelling a chain where wealth concentrates into one
+/// address over time.
+pub struct WinnerLoser<'b, R: 'b> {
+    accounts: Vec<(AccountId, u64)>,
+    rng: &'b mut R,
+    winner: usize,
+    next_loser: usize,
+}
+
+impl<'b, R: Rng> WinnerLoser<'b, R> {
+    pub fn new(accounts: &[Account], rng: &'b mut R) -> Self {
+        let winner = rng.gen_range(0, accounts.len());
+        WinnerLoser { accounts: snapshot(accounts), rng, winner, next_loser: 0 }
+    }
+}
+
+impl<'b, R: Rng> Iterator for WinnerLoser<'b, R> {
+    type Item = (AccountId, AccountId, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let n = self.accounts.len();
+        if n < 2 {
+            return None;
+        }
+
+        if self.next_loser == self.winner {
+            self.next_loser = (self.next_loser + 1) % n;
+        }
+
+        let loser = self.next_loser;
+        self.next_loser = (self.next_loser + 1) % n;
+
+        let value = self.rng.gen_range(1, self.accounts[loser].1 / 10 + 2);
+
+        Some((self.accounts[loser].0.clone(), self.accounts[self.winner].0.clone(), value))
+    }
+}
+
+/// A small set of senders originates most transfers: the sender's rank `k`
+/// (0 = heaviest hitter) is drawn with probability proportional to
+/// `1 / (k + 1)^s`, a Zipfian weighting controlled by `--gen-param s=<exponent>`
+/// (default `1.0`). The recipient is uniformly random among the rest.
+pub struct Zipfian<'b, R: 'b> {
+    accounts: Vec<(AccountId, u64)>,
+    rng: &'b mut R,
+    cumulative_weights: Vec<f64>,
+}
+
+impl<'b, R: Rng> Zipfian<'b, R> {
+    pub fn new(accounts: &[Account], rng: &'b mut R, params: &GeneratorParams) -> Self {
+        let exponent = param_f64(params, "s", 1.0);
+
+        let mut total = 0.0;
+        let cumulative_weights = (1..=accounts.len())
+            .map(|rank| {
+                total += 1.0 / (rank as f64).powf(exponent);
+                total
+            })
+            .collect();
+
+        Zipfian { accounts: snapshot(accounts), rng, cumulative_weights }
+    }
+
+    fn sample_rank(&mut self) -> usize {
+        let total = *self.cumulative_weights.last().expect("at least one account");
+        let target = self.rng.gen_range(0.0, total);
+        self.cumulative_weights.iter().position(|&w| target < w).unwrap_or(0)
+    }
+}
+
+impl<'b, R: Rng> Iterator for Zipfian<'b, R> {
+    type Item = (AccountId, AccountId, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let n = self.accounts.len();
+        if n < 2 {
+            return None;
+        }
+
+        let from_idx = self.sample_rank();
+        let to_idx = loop {
+            let candidate = self.rng.gen_range(0, n);
+            if candidate != from_idx {
+                break candidate;
+            }
+        };
+
+        let max_value = 2 * self.accounts[from_idx].1 + 1;
+        let value = self.rng.gen_range(0, max_value);
+
+        Some((self.accounts[from_idx].0.clone(), self.accounts[to_idx].0.clone(), value))
+    }
+}
+
+/// Alternates idle and high-volume windows instead of a steady rate.
+/// `--gen-param burst-len=<n>` (default 10) and `--gen-param idle-len=<n>`
+/// (default 10) control the window sizes; during an idle window the
+/// generator still yields transactions (callers drive count/rate, not
+/// wall-clock time) but zero-value ones, simulating near-silence.
+pub struct Burst<'b, R: 'b> {
+    inner: RandomTransactions<'b, R>,
+    burst_len: usize,
+    idle_len: usize,
+    position: usize,
+}
+
+impl<'b, R: Rng> Burst<'b, R> {
+    pub fn new(accounts: &[Account], rng: &'b mut R, params: &GeneratorParams) -> Self {
+        let burst_len = param_usize(params, "burst-len", 10);
+        let idle_len = param_usize(params, "idle-len", 10);
+
+        Burst { inner: RandomTransactions::new(accounts, rng), burst_len, idle_len, position: 0 }
+    }
+
+    fn in_burst_window(&self) -> bool {
+        let period = self.burst_len + self.idle_len;
+        if period == 0 {
+            return true;
+        }
+        (self.position % period) < self.burst_len
+    }
+}
+
+impl<'b, R: Rng> Iterator for Burst<'b, R> {
+    type Item = (AccountId, AccountId, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let in_burst = self.in_burst_window();
+        self.position += 1;
+
+        self.inner.next().map(|(from, to, value)| {
+            if in_burst { (from, to, value) } else { (from, to, 0) }
+        })
+    }
+}
@@ -0,0 +1,153 @@
+//! Generates secp256k1 keypairs and writes them out as a `Config`-compatible
+//! JSON file, so a load test doesn't need a pre-provisioned node or a
+//! hand-authored `accounts` list.
+
+use std::fs::File;
+
+use clap::ArgMatches;
+use rand::Rng;
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use tiny_keccak::keccak256;
+
+use signing;
+
+/// Number of keccak-256 rounds applied to a brain wallet phrase to derive its
+/// secret scalar. Intentionally small: this mode trades brute-force
+/// resistance for reproducibility, it is meant for disposable test fixtures.
+const BRAIN_WALLET_ROUNDS: usize = 16;
+
+const DEFAULT_COUNT: usize = 10;
+const DEFAULT_MIN_BALANCE: u64 = 1_000_000_000;
+const DEFAULT_MAX_BALANCE: u64 = 1_000_000_000_000;
+
+#[derive(Debug, Serialize)]
+struct AccountOut {
+    id: String,
+    balance: String,
+    password: String,
+    secret: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ConfigOut {
+    accounts: Vec<AccountOut>,
+}
+
+pub fn run<R: Rng>(matches: &ArgMatches, mut rng: R) {
+    let count = matches.value_of("count")
+        .map(|v| v.parse().expect("count must be a number"))
+        .unwrap_or(DEFAULT_COUNT);
+    let output_file = matches.value_of("output").unwrap_or("accounts.json");
+    let vanity = matches.value_of("vanity");
+    let brain = matches.value_of("brain");
+
+    let secp = Secp256k1::new();
+
+    let keypairs: Vec<_> = if let Some(phrase) = brain {
+        vec![brain_wallet(&secp, phrase)]
+    } else {
+        (0..count)
+            .map(|_| match vanity {
+                Some(prefix) => vanity_keypair(&secp, prefix, &mut rng),
+                None => random_keypair(&secp, &mut rng),
+            })
+            .collect()
+    };
+
+    let accounts = keypairs.into_iter()
+        .map(|(secret, address)| AccountOut {
+            id: address,
+            balance: rng.gen_range(DEFAULT_MIN_BALANCE, DEFAULT_MAX_BALANCE).to_string(),
+            password: format!("{:016x}", rng.gen::<u64>()),
+            secret: signing::to_hex(&secret[..]),
+        })
+        .collect();
+
+    let config = ConfigOut { accounts };
+
+    let output = File::create(output_file).expect("Unable to create output file");
+    ::serde_json::to_writer_pretty(output, &config).expect("Unable to write config");
+    println!("Wrote {} account(s) to {}", config.accounts.len(), output_file);
+}
+
+/// Generates a uniformly random keypair.
+fn random_keypair<R: Rng>(secp: &Secp256k1, rng: &mut R) -> (SecretKey, String) {
+    loop {
+        let mut bytes = [0u8; 32];
+        rng.fill_bytes(&mut bytes);
+        if let Ok(secret) = SecretKey::from_slice(secp, &bytes) {
+            let address = address_of(secp, &secret);
+            return (secret, address);
+        }
+    }
+}
+
+/// Brute-forces keypairs until the resulting address matches `prefix`
+/// (case-insensitive), reusing the caller's seeded RNG so a run is
+/// reproducible given the same `--seed`.
+fn vanity_keypair<R: Rng>(secp: &Secp256k1, prefix: &str, rng: &mut R) -> (SecretKey, String) {
+    let prefix = prefix.trim_start_matches("0x").to_lowercase();
+    loop {
+        let (secret, address) = random_keypair(secp, rng);
+        if address[2..].to_lowercase().starts_with(&prefix) {
+            return (secret, address);
+        }
+    }
+}
+
+/// Deterministically derives a keypair from a passphrase by iterating
+/// keccak-256 over the phrase `BRAIN_WALLET_ROUNDS` times to produce the
+/// secret scalar. Re-hashes on the rare occasion the scalar isn't a valid
+/// secp256k1 secret key.
+fn brain_wallet(secp: &Secp256k1, phrase: &str) -> (SecretKey, String) {
+    let mut hash = keccak256(phrase.as_bytes());
+    for _ in 1..BRAIN_WALLET_ROUNDS {
+        hash = keccak256(&hash);
+    }
+
+    let secret = loop {
+        match SecretKey::from_slice(secp, &hash) {
+            Ok(secret) => break secret,
+            Err(_) => hash = keccak256(&hash),
+        }
+    };
+
+    let address = address_of(secp, &secret);
+    (secret, address)
+}
+
+fn address_of(secp: &Secp256k1, secret: &SecretKey) -> String {
+    let public = PublicKey::from_secret_key(secp, secret)
+        .expect("public key derivation cannot fail for a valid secret key; qed");
+    let uncompressed = public.serialize_uncompressed();
+    // Skip the leading 0x04 tag; the address is the last 20 bytes of
+    // keccak256(x || y).
+    let hash = keccak256(&uncompressed[1..]);
+    signing::to_hex(&hash[12..])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn brain_wallet_is_deterministic_in_its_address() {
+        let secp = Secp256k1::new();
+
+        let (_, address) = brain_wallet(&secp, "correct horse battery staple");
+        let (_, address_again) = brain_wallet(&secp, "correct horse battery staple");
+
+        assert_eq!(address, address_again);
+        assert_eq!(address, "0xdf14b74cb6fb641c5935c2b87d6ffb62a2fc2a64");
+    }
+
+    #[test]
+    fn brain_wallet_differs_by_phrase() {
+        let secp = Secp256k1::new();
+
+        let (_, a) = brain_wallet(&secp, "phrase a");
+        let (_, b) = brain_wallet(&secp, "phrase b");
+
+        assert_ne!(a, b);
+    }
+}
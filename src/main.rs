@@ -5,18 +5,30 @@ extern crate serde_derive;
 extern crate serde_json;
 
 extern crate rand;
+extern crate reqwest;
+extern crate rlp;
+extern crate secp256k1;
+extern crate tiny_keccak;
 
 use std::collections::HashMap;
 use std::fs::File;
 
-use clap::{Arg, App};
+use clap::{Arg, App, SubCommand};
+use secp256k1::{Secp256k1, SecretKey};
 use serde::Serialize;
 use rand::{Rng, SeedableRng};
 
 mod generator;
+mod keygen;
+mod signing;
+mod submit;
 
 static JSONRPC_VERSION: &str = "2.0";
 static METHOD_NAME: &str = "personal_sendTransaction";
+static RAW_METHOD_NAME: &str = "eth_sendRawTransaction";
+
+const DEFAULT_GAS_PRICE: u64 = 20_000_000_000;
+const DEFAULT_GAS: u64 = 21_000;
 
 #[derive(Debug, Clone, PartialEq, Serialize)]
 struct Wrapper<P: Serialize> {
@@ -55,10 +67,37 @@ struct Transaction {
     value: String,
 }
 
-#[derive(Debug)]
+type EthSendRawTransaction = Wrapper<EthSendRawTransactionParams>;
+
+impl EthSendRawTransaction {
+    fn new(params: EthSendRawTransactionParams, id: RpcId) -> Self {
+        Wrapper {
+            jsonrpc: JSONRPC_VERSION,
+            method: RAW_METHOD_NAME,
+            params, id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct EthSendRawTransactionParams(Vec<String>);
+
 pub struct Account {
     id: AccountId,
     balance: u64,
+    nonce: u64,
+    secret: Option<SecretKey>,
+}
+
+impl std::fmt::Debug for Account {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Account")
+            .field("id", &self.id)
+            .field("balance", &self.balance)
+            .field("nonce", &self.nonce)
+            .field("secret", &self.secret.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -66,6 +105,7 @@ struct AccountConfig {
     id: AccountId,
     balance: String,
     password: Password,
+    secret: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -77,6 +117,15 @@ struct ConfigFile {
     #[serde(rename = "chunk-size")]
     chunk_size: Option<usize>,
     seed: Option<usize>,
+    #[serde(rename = "sign-local")]
+    sign_local: Option<bool>,
+    #[serde(rename = "chain-id")]
+    chain_id: Option<u64>,
+    #[serde(rename = "gas-price")]
+    gas_price: Option<u64>,
+    gas: Option<u64>,
+    #[serde(rename = "account-start-nonce")]
+    account_start_nonce: Option<u64>,
     accounts: Vec<AccountConfig>,
 }
 
@@ -87,6 +136,11 @@ struct Config {
     filter_from: Option<String>,
     chunk_size: Option<usize>,
     seed: Option<usize>,
+    sign_local: Option<bool>,
+    chain_id: Option<u64>,
+    gas_price: Option<u64>,
+    gas: Option<u64>,
+    account_start_nonce: Option<u64>,
     accounts: Vec<Account>,
     passwords: HashMap<AccountId, Password>,
 }
@@ -101,23 +155,34 @@ fn parse_config_file(config_file: &str) -> Config {
     let filter_from = config.filter_from;
     let chunk_size = config.chunk_size;
     let seed = config.seed;
+    let sign_local = config.sign_local;
+    let chain_id = config.chain_id;
+    let gas_price = config.gas_price;
+    let gas = config.gas;
+    let account_start_nonce = config.account_start_nonce;
 
     let passwords =
         config.accounts.iter()
         .map(|conf| (conf.id.clone(), conf.password.clone()))
         .collect();
 
+    let secp = Secp256k1::new();
     let accounts =
         config.accounts.into_iter()
         .map(|conf| {
             Account {
                 id: conf.id,
                 balance: conf.balance.parse().expect("Unable to parse balance"),
+                nonce: 0,
+                secret: conf.secret.as_ref().map(|hex| signing::parse_secret_key(&secp, hex)),
             }
         })
         .collect();
 
-    Config { generator, count, filter_from, chunk_size, seed, accounts, passwords }
+    Config {
+        generator, count, filter_from, chunk_size, seed, sign_local, chain_id, gas_price, gas, account_start_nonce,
+        accounts, passwords,
+    }
 }
 
 fn main() {
@@ -153,12 +218,106 @@ fn main() {
              .long("seed")
              .value_name("N")
              .takes_value(true))
+        .arg(Arg::with_name("sign-local")
+             .long("sign-local")
+             .help("Sign transactions client-side and emit eth_sendRawTransaction requests"))
+        .arg(Arg::with_name("chain-id")
+             .long("chain-id")
+             .value_name("N")
+             .takes_value(true))
+        .arg(Arg::with_name("gas-price")
+             .long("gas-price")
+             .value_name("WEI")
+             .takes_value(true)
+             .help("Gas price for --sign-local transactions (default 20 Gwei)"))
+        .arg(Arg::with_name("gas")
+             .long("gas")
+             .value_name("N")
+             .takes_value(true)
+             .help("Gas limit for --sign-local transactions (default 21000)"))
+        .arg(Arg::with_name("submit")
+             .long("submit")
+             .value_name("URL")
+             .takes_value(true)
+             .help("POST generated batches to a live JSON-RPC node, in addition to writing them to disk"))
+        .arg(Arg::with_name("concurrency")
+             .long("concurrency")
+             .value_name("N")
+             .default_value("1")
+             .takes_value(true))
+        .arg(Arg::with_name("replay")
+             .long("replay")
+             .requires("submit")
+             .help("Skip generation and submit the rpc.json.N files already on disk at --output"))
+        .arg(Arg::with_name("gen-param")
+             .long("gen-param")
+             .value_name("KEY=VALUE")
+             .takes_value(true)
+             .multiple(true)
+             .help("Extra parameter for the selected generator, e.g. s=1.2 for --generator zipfian"))
+        .arg(Arg::with_name("account-start-nonce")
+             .long("account-start-nonce")
+             .value_name("N")
+             .takes_value(true))
+        .arg(Arg::with_name("strict-balance")
+             .long("strict-balance")
+             .help("Abort with a nonzero exit code instead of skipping a transaction that would overdraw its sender"))
+        .subcommand(SubCommand::with_name("keygen")
+             .about("Generate secp256k1 keypairs into a Config-compatible JSON file")
+             .arg(Arg::with_name("count")
+                  .long("count")
+                  .short("n")
+                  .value_name("N")
+                  .takes_value(true))
+             .arg(Arg::with_name("output")
+                  .long("output")
+                  .short("o")
+                  .value_name("OUTPUT")
+                  .default_value("accounts.json")
+                  .takes_value(true))
+             .arg(Arg::with_name("vanity")
+                  .long("vanity")
+                  .value_name("PREFIX")
+                  .takes_value(true))
+             .arg(Arg::with_name("brain")
+                  .long("brain")
+                  .value_name("PHRASE")
+                  .takes_value(true))
+             .arg(Arg::with_name("seed")
+                  .long("seed")
+                  .value_name("N")
+                  .takes_value(true)))
         .get_matches();
 
-    let config_file = matches.value_of("config").expect("Must provide config file");
+    if let Some(matches) = matches.subcommand_matches("keygen") {
+        let seed = matches.value_of("seed")
+            .map(|s| s.parse().expect("Unable to parse seed"))
+            .unwrap_or_else(|| rand::thread_rng().gen());
+        println!("Used seed {}", seed);
+
+        let rng = rand::StdRng::from_seed(&[seed]);
+        keygen::run(matches, rng);
+        return;
+    }
+
     let output_file = matches.value_of("output").expect("Must provide output file");
+    let submit_url = matches.value_of("submit");
+    let concurrency = matches.value_of("concurrency")
+        .expect("--concurrency has a default value")
+        .parse()
+        .expect("concurrency must be a number");
+
+    if matches.is_present("replay") {
+        let url = submit_url.expect("--replay requires --submit");
+        let batches = submit::read_batches(output_file);
+        println!("Replaying {} batch(es) from {}.N against {}", batches.len(), output_file, url);
+        submit::submit_all(url, batches, concurrency);
+        return;
+    }
+
+    let config_file = matches.value_of("config").expect("Must provide config file");
 
-    let mut config = parse_config_file(&config_file);
+    let mut config = parse_config_file(config_file);
 
     let generator_arg = matches.value_of("generator").map(Into::into);
     let count_arg = matches.value_of("transactions")
@@ -170,45 +329,179 @@ fn main() {
     let chunks_arg =
         matches.value_of("chunk-size")
         .map(|s| s.parse().expect("Unable to parse chunk size"));
+    let sign_local_arg = if matches.is_present("sign-local") { Some(true) } else { None };
+    let chain_id_arg =
+        matches.value_of("chain-id")
+        .map(|s| s.parse().expect("Unable to parse chain id"));
+    let gas_price_arg =
+        matches.value_of("gas-price")
+        .map(|s| s.parse().expect("Unable to parse gas price"));
+    let gas_arg =
+        matches.value_of("gas")
+        .map(|s| s.parse().expect("Unable to parse gas"));
+    let gen_params: generator::GeneratorParams = matches.values_of("gen-param")
+        .map(|values| values.map(|kv| {
+            let mut parts = kv.splitn(2, '=');
+            let key = parts.next().expect("split always yields at least one part");
+            let value = parts.next().unwrap_or_else(|| panic!("--gen-param {} is missing a value", kv));
+            (key.to_string(), value.to_string())
+        }).collect())
+        .unwrap_or_default();
+    let account_start_nonce_arg =
+        matches.value_of("account-start-nonce")
+        .map(|s| s.parse().expect("Unable to parse account start nonce"));
+    let strict_balance = matches.is_present("strict-balance");
 
     config.generator = generator_arg.or(config.generator);
     config.count = count_arg.or(config.count);
     config.seed = seed_arg.or(config.seed);
     config.filter_from = filter_arg.or(config.filter_from);
     config.chunk_size = chunks_arg.or(config.chunk_size);
+    config.sign_local = sign_local_arg.or(config.sign_local);
+    config.chain_id = chain_id_arg.or(config.chain_id);
+    config.gas_price = gas_price_arg.or(config.gas_price);
+    config.gas = gas_arg.or(config.gas);
+    config.account_start_nonce = account_start_nonce_arg.or(config.account_start_nonce);
 
     let generator = config.generator.unwrap_or("random".into());
     let seed = config.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let sign_local = config.sign_local.unwrap_or(false);
+    let start_nonce = config.account_start_nonce.unwrap_or(0);
+
+    for account in &mut config.accounts {
+        account.nonce = start_nonce;
+    }
 
     let rng = rand::StdRng::from_seed(&[seed]);
     println!("Used seed {}", seed);
 
-    let transactions: Vec<_> = generate_transactions(
-        &generator,
-        &mut config.accounts,
-        rng,
-        config.count,
-        config.filter_from.map(AccountId),
-        &config.passwords,
+    let (transaction_count, rejected) = if sign_local {
+        let chain_id = config.chain_id.expect("--chain-id is required with --sign-local");
+        let gas_price = config.gas_price.unwrap_or(DEFAULT_GAS_PRICE);
+        let gas = config.gas.unwrap_or(DEFAULT_GAS);
+
+        let (transactions, rejected) = generate_signed_transactions(
+            &generator,
+            &mut config.accounts,
+            rng,
+            config.count,
+            config.filter_from.map(AccountId),
+            chain_id,
+            gas_price,
+            gas,
+            &gen_params,
+            strict_balance,
+        );
+
+        write_chunks(&transactions, output_file, config.chunk_size, submit_url, concurrency);
+        (transactions.len(), rejected)
+    } else {
+        let (transactions, rejected) = generate_transactions(
+            &generator,
+            &mut config.accounts,
+            rng,
+            config.count,
+            config.filter_from.map(AccountId),
+            &config.passwords,
+            &gen_params,
+            strict_balance,
+        );
+
+        write_chunks(&transactions, output_file, config.chunk_size, submit_url, concurrency);
+        (transactions.len(), rejected)
+    };
+
+    println!(
+        "Final balances after {} transactions ({} rejected for insufficient balance) using the {} generator:",
+        transaction_count, rejected, generator,
     );
+    for account in &config.accounts {
+        println!("{}:\t{}", account.id.0, account.balance);
+    }
+}
+
+fn write_chunks<T: Serialize>(
+    transactions: &[T],
+    output_file: &str,
+    chunk_size: Option<usize>,
+    submit_url: Option<&str>,
+    concurrency: usize,
+) {
+    if transactions.is_empty() {
+        println!("0 transactions; nothing to write");
+        return;
+    }
 
-    let chunk_size = config.chunk_size.unwrap_or_else(|| transactions.len());
+    let chunk_size = chunk_size.unwrap_or(transactions.len());
+    let mut batches = Vec::new();
 
     for (i, chunk) in transactions.chunks(chunk_size).enumerate() {
         let output_file = format!("{}.{}", output_file, i);
-        let transactions = chunk;
 
         let output = File::create(&output_file).expect("Unable to create output file");
-        serde_json::to_writer(output, &transactions).expect("Unable to convert to JSON");
+        serde_json::to_writer(output, &chunk).expect("Unable to convert to JSON");
         println!("RPC body written to {}", output_file);
+
+        if submit_url.is_some() {
+            let value = serde_json::to_value(chunk).expect("Unable to convert to JSON");
+            let batch = match value {
+                serde_json::Value::Array(batch) => batch,
+                _ => unreachable!("a slice always serializes to a JSON array"),
+            };
+            batches.push(batch);
+        }
     }
 
-    println!("Final balances after {} transactions using the {} generator:", transactions.len(), generator);
-    for account in &config.accounts {
-        println!("{}:\t{}", account.id.0, account.balance);
+    if let Some(url) = submit_url {
+        submit::submit_all(url, batches, concurrency);
+    }
+}
+
+fn build_generator<'a, R>(
+    generator_type: &str,
+    accounts: &[Account],
+    rng: &'a mut R,
+    gen_params: &generator::GeneratorParams,
+) -> Box<dyn generator::TransactionGenerator + 'a>
+where
+    R: rand::Rng,
+{
+    match generator_type {
+        "random" => Box::new(generator::RandomTransactions::new(accounts, rng)),
+        "winner-loser" => Box::new(generator::WinnerLoser::new(accounts, rng)),
+        "zipfian" => Box::new(generator::Zipfian::new(accounts, rng, gen_params)),
+        "burst" => Box::new(generator::Burst::new(accounts, rng, gen_params)),
+        _ => panic!("Unknown generator type {}", generator_type),
+    }
+}
+
+/// Applies `value` to the `from` account if its current balance can cover
+/// it, deducting the balance and bumping the nonce. Returns the nonce the
+/// transfer was sent with, or `None` if it was rejected as an overdraft.
+fn try_apply_transfer(accounts: &mut [Account], from: &AccountId, value: u64) -> Option<u64> {
+    let account = accounts.iter_mut().find(|a| &a.id == from)
+        .unwrap_or_else(|| panic!("Generator produced unknown sender account {}", from.0));
+
+    if value > account.balance {
+        return None;
+    }
+
+    let nonce = account.nonce;
+    account.balance -= value;
+    account.nonce += 1;
+    Some(nonce)
+}
+
+fn reject_overdraft(from: &AccountId, value: u64, strict_balance: bool) {
+    let message = format!("Rejecting transfer of {} from {}: exceeds current balance", value, from.0);
+    if strict_balance {
+        eprintln!("{} (--strict-balance)", message);
+        ::std::process::exit(1);
     }
+    println!("{}", message);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn generate_transactions<R>(
     generator_type: &str,
     accounts: &mut [Account],
@@ -216,39 +509,101 @@ fn generate_transactions<R>(
     count: Option<usize>,
     filter_from: Option<AccountId>,
     passwords: &HashMap<AccountId, Password>,
-) -> Vec<PersonalSendTransaction>
+    gen_params: &generator::GeneratorParams,
+    strict_balance: bool,
+) -> (Vec<PersonalSendTransaction>, usize)
 where
     R: rand::Rng,
 {
-    let generator: Box<Iterator<Item = _>> = match generator_type {
-        "random" => {
-            Box::new(generator::RandomTransactions::new(accounts, &mut rng))
-        }
-        "winner-loser" => {
-            Box::new(generator::WinnerLoser::new(accounts, &mut rng))
-        }
-        _ => panic!("Unknown generator type {}", generator_type),
+    let generator = build_generator(generator_type, accounts, &mut rng, gen_params);
+
+    let generator = match count {
+        Some(count) => Box::new(generator.take(count)),
+        None => generator,
+    };
+
+    let generator = match filter_from {
+        Some(filter_from) => Box::new(generator.filter(move |(from, _, _)| from == &filter_from)),
+        None => generator,
     };
 
+    let mut transactions = Vec::new();
+    let mut rejected = 0;
+
+    for (from, to, value) in generator {
+        if try_apply_transfer(accounts, &from, value).is_none() {
+            reject_overdraft(&from, value, strict_balance);
+            rejected += 1;
+            continue;
+        }
+
+        let password = passwords[&from].clone();
+        let transaction = Transaction { from, to, value: format!("0x{:x}", value) };
+        let params = PersonalSendTransactionParams(transaction, password);
+        transactions.push(PersonalSendTransaction::new(params, transactions.len()));
+    }
+
+    (transactions, rejected)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn generate_signed_transactions<R>(
+    generator_type: &str,
+    accounts: &mut [Account],
+    mut rng: R,
+    count: Option<usize>,
+    filter_from: Option<AccountId>,
+    chain_id: u64,
+    gas_price: u64,
+    gas: u64,
+    gen_params: &generator::GeneratorParams,
+    strict_balance: bool,
+) -> (Vec<EthSendRawTransaction>, usize)
+where
+    R: rand::Rng,
+{
+    let secrets: HashMap<_, _> = accounts.iter()
+        .filter_map(|account| account.secret.as_ref().map(|secret| (account.id.clone(), *secret)))
+        .collect();
+
+    let generator = build_generator(generator_type, accounts, &mut rng, gen_params);
+
     let generator = match count {
         Some(count) => Box::new(generator.take(count)),
         None => generator,
     };
 
     let generator = match filter_from {
-        Some(filter_from) => Box::new(generator.filter(move |&(ref from, _, _)| from == &filter_from)),
+        Some(filter_from) => Box::new(generator.filter(move |(from, _, _)| from == &filter_from)),
         None => generator,
     };
 
-    generator
-        .enumerate()
-        .map(|(id, (from, to, value))| {
-            let password = passwords[&from].clone();
-            let transaction = Transaction { from, to, value: format!("0x{:x}", value) };
-            let params = PersonalSendTransactionParams(transaction, password);
-            PersonalSendTransaction::new(params, id)
-        })
-        .collect()
+    let secp = Secp256k1::new();
+    let mut transactions = Vec::new();
+    let mut rejected = 0;
+
+    for (from, to, value) in generator {
+        let nonce = match try_apply_transfer(accounts, &from, value) {
+            Some(nonce) => nonce,
+            None => {
+                reject_overdraft(&from, value, strict_balance);
+                rejected += 1;
+                continue;
+            }
+        };
+
+        let secret = secrets.get(&from)
+            .unwrap_or_else(|| panic!("No secret key configured for account {}", from.0));
+
+        let raw = signing::sign_transaction(
+            &secp, secret, nonce, gas_price, gas, &to, value, &[], chain_id,
+        );
+
+        let params = EthSendRawTransactionParams(vec![signing::to_hex(&raw)]);
+        transactions.push(EthSendRawTransaction::new(params, transactions.len()));
+    }
+
+    (transactions, rejected)
 }
 
 #[cfg(test)]
@@ -262,8 +617,8 @@ mod test {
         let value = "0xde0b6b3a7640000";
 
         let transaction = Transaction {
-            from: from,
-            to: to,
+            from,
+            to,
             value: value.into(),
         };
 
@@ -283,36 +638,120 @@ mod test {
     fn random_transactions() {
         let mut rng = rand::isaac::Isaac64Rng::from_seed(&[1,2,3,4]);
 
-        let mut accounts = vec![
+        let accounts = vec![
             Account {
                 id: AccountId("a".into()),
                 balance: 1000,
+                nonce: 0,
+                secret: None,
             },
             Account {
                 id: AccountId("b".into()),
                 balance: 1000,
+                nonce: 0,
+                secret: None,
             },
         ];
 
         let transactions: Vec<_> =
-            TransactionGenerator::new(&mut accounts, &mut rng)
+            generator::RandomTransactions::new(&accounts, &mut rng)
             .take(10)
             .collect();
 
         assert_eq!(
             transactions,
             [
-                (AccountId("a".into()), AccountId("b".into()), 594),
-                (AccountId("b".into()), AccountId("a".into()), 1300),
-                (AccountId("b".into()), AccountId("a".into()), 24),
-                (AccountId("a".into()), AccountId("b".into()), 1240),
-                (AccountId("b".into()), AccountId("a".into()), 1443),
-                (AccountId("b".into()), AccountId("a".into()), 42),
-                (AccountId("a".into()), AccountId("b".into()), 1347),
-                (AccountId("a".into()), AccountId("b".into()), 94),
-                (AccountId("b".into()), AccountId("a".into()), 596),
-                (AccountId("a".into()), AccountId("b".into()), 503),
+                (AccountId("a".into()), AccountId("b".into()), 725),
+                (AccountId("a".into()), AccountId("b".into()), 1546),
+                (AccountId("a".into()), AccountId("b".into()), 569),
+                (AccountId("a".into()), AccountId("b".into()), 751),
+                (AccountId("a".into()), AccountId("b".into()), 537),
+                (AccountId("a".into()), AccountId("b".into()), 151),
+                (AccountId("a".into()), AccountId("b".into()), 668),
+                (AccountId("a".into()), AccountId("b".into()), 715),
+                (AccountId("a".into()), AccountId("b".into()), 1080),
+                (AccountId("b".into()), AccountId("a".into()), 488),
             ]
         );
     }
+
+    fn three_accounts() -> Vec<Account> {
+        vec![
+            Account { id: AccountId("a".into()), balance: 1000, nonce: 0, secret: None },
+            Account { id: AccountId("b".into()), balance: 1000, nonce: 0, secret: None },
+            Account { id: AccountId("c".into()), balance: 1000, nonce: 0, secret: None },
+        ]
+    }
+
+    #[test]
+    fn zipfian_heaviest_rank_dominates_senders() {
+        let mut rng = rand::isaac::Isaac64Rng::from_seed(&[1, 2, 3, 4]);
+        let accounts = three_accounts();
+        let params = generator::GeneratorParams::new();
+
+        let transactions: Vec<_> =
+            generator::Zipfian::new(&accounts, &mut rng, &params)
+            .take(20)
+            .collect();
+
+        // Rank 0 ("a", the heaviest sender under the default s=1.0 weighting)
+        // should originate most of the 20 transfers.
+        let from_a = transactions.iter().filter(|&(from, _, _)| from == &AccountId("a".into())).count();
+        assert!(from_a > 10, "expected rank 0 to dominate senders, got {} of 20", from_a);
+
+        for (from, to, _) in &transactions {
+            assert_ne!(from, to);
+        }
+    }
+
+    #[test]
+    fn burst_zeroes_values_outside_the_burst_window() {
+        let mut rng_plain = rand::isaac::Isaac64Rng::from_seed(&[5, 6, 7, 8]);
+        let mut rng_burst = rand::isaac::Isaac64Rng::from_seed(&[5, 6, 7, 8]);
+        let accounts = three_accounts();
+
+        let mut params = generator::GeneratorParams::new();
+        params.insert("burst-len".into(), "2".into());
+        params.insert("idle-len".into(), "3".into());
+
+        let plain: Vec<_> = generator::RandomTransactions::new(&accounts, &mut rng_plain).take(15).collect();
+        let burst: Vec<_> = generator::Burst::new(&accounts, &mut rng_burst, &params).take(15).collect();
+
+        for (i, (plain, burst)) in plain.iter().zip(burst.iter()).enumerate() {
+            assert_eq!(plain.0, burst.0);
+            assert_eq!(plain.1, burst.1);
+
+            if i % 5 < 2 {
+                assert_eq!(burst.2, plain.2, "position {} is inside the burst window", i);
+            } else {
+                assert_eq!(burst.2, 0, "position {} is outside the burst window", i);
+            }
+        }
+    }
+
+    #[test]
+    fn try_apply_transfer_deducts_balance_and_assigns_sequential_nonces() {
+        let mut accounts = three_accounts();
+        let from = AccountId("a".into());
+
+        let first_nonce = try_apply_transfer(&mut accounts, &from, 400).expect("affordable transfer");
+        let second_nonce = try_apply_transfer(&mut accounts, &from, 400).expect("affordable transfer");
+
+        assert_eq!(first_nonce, 0);
+        assert_eq!(second_nonce, 1);
+        assert_eq!(accounts[0].balance, 200);
+        assert_eq!(accounts[0].nonce, 2);
+    }
+
+    #[test]
+    fn try_apply_transfer_rejects_overdrafts_without_mutating_state() {
+        let mut accounts = three_accounts();
+        let from = AccountId("a".into());
+
+        let result = try_apply_transfer(&mut accounts, &from, 1001);
+
+        assert_eq!(result, None);
+        assert_eq!(accounts[0].balance, 1000);
+        assert_eq!(accounts[0].nonce, 0);
+    }
 }
@@ -0,0 +1,172 @@
+//! Client-side transaction signing for the `eth_sendRawTransaction` output mode.
+//!
+//! This builds and signs transactions the way a node would, so the generated
+//! batch can be replayed against any JSON-RPC endpoint, not just one that
+//! holds the sender's key in its own keystore.
+
+use rlp::RlpStream;
+use secp256k1::{Message, Secp256k1, SecretKey};
+use tiny_keccak::keccak256;
+
+use AccountId;
+
+/// RLP-encodes and signs a transaction per EIP-155, returning the raw bytes
+/// suitable for `eth_sendRawTransaction`.
+#[allow(clippy::too_many_arguments)]
+pub fn sign_transaction(
+    secp: &Secp256k1,
+    secret: &SecretKey,
+    nonce: u64,
+    gas_price: u64,
+    gas: u64,
+    to: &AccountId,
+    value: u64,
+    data: &[u8],
+    chain_id: u64,
+) -> Vec<u8> {
+    let to = parse_address(&to.0);
+    let unsigned_hash = keccak256(&rlp_body(nonce, gas_price, gas, &to, value, data, chain_id, 0, 0));
+
+    let message = Message::from_slice(&unsigned_hash).expect("hash is 32 bytes; qed");
+    let signature = secp.sign_recoverable(&message, secret).expect("signing with a valid key cannot fail");
+    let (recovery_id, compact) = signature.serialize_compact(secp);
+
+    let r = &compact[0..32];
+    let s = &compact[32..64];
+    let v = recovery_id.to_i32() as u64 + chain_id * 2 + 35;
+
+    let mut stream = RlpStream::new_list(9);
+    stream.append(&nonce);
+    stream.append(&gas_price);
+    stream.append(&gas);
+    stream.append(&to.as_ref());
+    stream.append(&value);
+    stream.append(&data);
+    stream.append(&v);
+    stream.append(&r);
+    stream.append(&s);
+    stream.out()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn rlp_body(
+    nonce: u64,
+    gas_price: u64,
+    gas: u64,
+    to: &[u8; 20],
+    value: u64,
+    data: &[u8],
+    chain_id: u64,
+    empty_r: u8,
+    empty_s: u8,
+) -> Vec<u8> {
+    let mut stream = RlpStream::new_list(9);
+    stream.append(&nonce);
+    stream.append(&gas_price);
+    stream.append(&gas);
+    stream.append(&to.as_ref());
+    stream.append(&value);
+    stream.append(&data);
+    stream.append(&chain_id);
+    stream.append(&empty_r);
+    stream.append(&empty_s);
+    stream.out()
+}
+
+/// Parses a secp256k1 secret key from a (optionally `0x`-prefixed) hex string.
+pub fn parse_secret_key(secp: &Secp256k1, hex: &str) -> SecretKey {
+    let bytes = parse_hex(hex);
+    SecretKey::from_slice(secp, &bytes).expect("invalid secret key hex")
+}
+
+fn parse_address(hex: &str) -> [u8; 20] {
+    let bytes = parse_hex(hex);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&bytes[..20]);
+    address
+}
+
+fn parse_hex(hex: &str) -> Vec<u8> {
+    let hex = hex.trim_start_matches("0x");
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).expect("invalid hex"))
+        .collect()
+}
+
+/// Hex-encodes `bytes` with a `0x` prefix, as expected by `eth_sendRawTransaction`.
+pub fn to_hex(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(2 + bytes.len() * 2);
+    out.push_str("0x");
+    for byte in bytes {
+        out.push_str(&format!("{:02x}", byte));
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use secp256k1::{PublicKey, RecoverableSignature, RecoveryId};
+    use tiny_keccak::keccak256;
+
+    use super::*;
+
+    fn address_of(secp: &Secp256k1, secret: &SecretKey) -> [u8; 20] {
+        let public = PublicKey::from_secret_key(secp, secret).expect("valid secret key");
+        let uncompressed = public.serialize_uncompressed();
+        let hash = keccak256(&uncompressed[1..]);
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&hash[12..]);
+        address
+    }
+
+    /// Signs a known transaction and decodes the raw RLP back apart,
+    /// recovering the signer's public key from (v, r, s) per EIP-155 and
+    /// checking it matches the sender's actual address. A wrong field order,
+    /// a wrong `v` offset, or a wrong recovery id would all make this
+    /// recovery land on the wrong address (or fail outright).
+    #[test]
+    fn sign_transaction_recovers_to_the_signing_address() {
+        let secp = Secp256k1::new();
+        let secret = parse_secret_key(&secp, "0x4646464646464646464646464646464646464646464646464646464646464646");
+        let to = AccountId("0x3535353535353535353535353535353535353535".into());
+        let chain_id = 1;
+
+        let raw = sign_transaction(&secp, &secret, 9, 20_000_000_000, 21_000, &to, 1_000_000_000_000_000_000, &[], chain_id);
+
+        let decoded = ::rlp::Rlp::new(&raw);
+        let nonce: u64 = decoded.val_at(0).expect("nonce");
+        let gas_price: u64 = decoded.val_at(1).expect("gas price");
+        let gas: u64 = decoded.val_at(2).expect("gas");
+        let decoded_to: Vec<u8> = decoded.val_at(3).expect("to");
+        let value: u64 = decoded.val_at(4).expect("value");
+        let v: u64 = decoded.val_at(6).expect("v");
+        let r: Vec<u8> = decoded.val_at(7).expect("r");
+        let s: Vec<u8> = decoded.val_at(8).expect("s");
+
+        assert_eq!(nonce, 9);
+        assert_eq!(gas_price, 20_000_000_000);
+        assert_eq!(gas, 21_000);
+        assert_eq!(decoded_to, parse_address(&to.0).to_vec());
+        assert_eq!(value, 1_000_000_000_000_000_000);
+
+        let recovery_id = RecoveryId::from_i32((v - chain_id * 2 - 35) as i32).expect("valid recovery id");
+        let mut compact = [0u8; 64];
+        compact[..32].copy_from_slice(&left_pad_32(&r));
+        compact[32..].copy_from_slice(&left_pad_32(&s));
+        let signature = RecoverableSignature::from_compact(&secp, &compact, recovery_id).expect("valid signature");
+
+        let unsigned_hash = keccak256(&rlp_body(nonce, gas_price, gas, &parse_address(&to.0), value, &[], chain_id, 0, 0));
+        let message = Message::from_slice(&unsigned_hash).expect("hash is 32 bytes; qed");
+        let recovered = secp.recover(&message, &signature).expect("recovery succeeds for a valid signature");
+
+        let recovered_address = keccak256(&recovered.serialize_uncompressed()[1..]);
+        assert_eq!(&recovered_address[12..], &address_of(&secp, &secret)[..]);
+    }
+
+    fn left_pad_32(bytes: &[u8]) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        out[32 - bytes.len()..].copy_from_slice(bytes);
+        out
+    }
+}
@@ -0,0 +1,123 @@
+//! Drives a batch of already-built JSON-RPC requests against a live
+//! Parity/OpenEthereum node, instead of only writing them to disk.
+//!
+//! Works uniformly over `serde_json::Value` batches so the same code path
+//! submits freshly generated transactions and replays `rpc.json.N` files
+//! written by an earlier run.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::Value;
+
+const MAX_ATTEMPTS: u32 = 5;
+const INITIAL_BACKOFF_MS: u64 = 200;
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    id: usize,
+    result: Option<Value>,
+    error: Option<Value>,
+}
+
+/// Submits each batch to `url`, using up to `concurrency` requests in
+/// flight at once. Prints each request's outcome (transaction hash or
+/// error) to stdout as responses arrive.
+pub fn submit_all(url: &str, batches: Vec<Vec<Value>>, concurrency: usize) {
+    let queue = Arc::new(Mutex::new(VecDeque::from(batches)));
+    let url = Arc::new(url.to_string());
+    let concurrency = concurrency.max(1);
+
+    let workers: Vec<_> = (0..concurrency)
+        .map(|_| {
+            let queue = queue.clone();
+            let url = url.clone();
+            thread::spawn(move || worker_loop(&url, queue))
+        })
+        .collect();
+
+    for worker in workers {
+        worker.join().expect("submit worker panicked");
+    }
+}
+
+fn worker_loop(url: &str, queue: Arc<Mutex<VecDeque<Vec<Value>>>>) {
+    let client = reqwest::Client::new();
+
+    loop {
+        let batch = match queue.lock().expect("queue lock poisoned").pop_front() {
+            Some(batch) => batch,
+            None => return,
+        };
+
+        match post_with_retry(&client, url, &batch) {
+            Ok(responses) => report(&responses),
+            Err(err) => println!("Batch of {} requests failed: {}", batch.len(), err),
+        }
+    }
+}
+
+fn post_with_retry(client: &reqwest::Client, url: &str, batch: &[Value]) -> Result<Vec<RpcResponse>, String> {
+    let mut backoff = Duration::from_millis(INITIAL_BACKOFF_MS);
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        match send_once(client, url, batch) {
+            Ok(parsed) => return Ok(parsed),
+            Err(err) => {
+                if attempt == MAX_ATTEMPTS {
+                    return Err(format!("{} (after {} attempts)", err, attempt));
+                }
+                thread::sleep(backoff);
+                backoff *= 2;
+            }
+        }
+    }
+
+    unreachable!("loop always returns by the last attempt")
+}
+
+/// Posts `batch` once and parses the response. A non-2xx status (e.g. a node
+/// returning 502/503 under load) is surfaced as an `Err` just like a
+/// transport-level failure, so `post_with_retry` retries it the same way.
+fn send_once(client: &reqwest::Client, url: &str, batch: &[Value]) -> Result<Vec<RpcResponse>, String> {
+    let mut response = client.post(url).json(batch).send().map_err(|err| err.to_string())?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("server returned HTTP {}", status));
+    }
+
+    response.json().map_err(|err| format!("Unable to parse response: {}", err))
+}
+
+fn report(responses: &[RpcResponse]) {
+    for response in responses {
+        match (&response.result, &response.error) {
+            (Some(hash), _) => println!("id {}: {}", response.id, hash),
+            (None, Some(error)) => println!("id {}: error: {}", response.id, error),
+            (None, None) => println!("id {}: no result or error in response", response.id),
+        }
+    }
+}
+
+/// Reads back `{output_file}.0`, `{output_file}.1`, ... as written by
+/// `write_chunks`, for replaying a previously generated run.
+pub fn read_batches(output_file: &str) -> Vec<Vec<Value>> {
+    let mut batches = Vec::new();
+
+    for i in 0.. {
+        let path = format!("{}.{}", output_file, i);
+        let file = match ::std::fs::File::open(&path) {
+            Ok(file) => file,
+            Err(_) => break,
+        };
+
+        let batch: Vec<Value> = ::serde_json::from_reader(file)
+            .unwrap_or_else(|err| panic!("Unable to parse {}: {}", path, err));
+        batches.push(batch);
+    }
+
+    batches
+}